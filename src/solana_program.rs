@@ -1,19 +1,30 @@
 use anyhow::anyhow;
+use bs58;
 use solana_cli_config::Config;
 use solana_client::{
     rpc_client::RpcClient,
     rpc_config::RpcProgramAccountsConfig,
     rpc_filter::{Memcmp, RpcFilterType},
+    tpu_client::{TpuClient, TpuClientConfig},
 };
+use solana_remote_wallet::remote_wallet::RemoteWalletManager;
 use std::{
     io::{self, Read, Write},
     str::FromStr,
+    sync::Arc,
 };
 
 use borsh::{to_vec, BorshDeserialize, BorshSerialize};
 use solana_sdk::{
-    instruction::AccountMeta, message::Message, pubkey::Pubkey, signature::Keypair, signer::Signer,
-    system_program, transaction::Transaction,
+    compute_budget::ComputeBudgetInstruction,
+    hash::Hash,
+    instruction::AccountMeta,
+    message::Message,
+    pubkey::Pubkey,
+    signature::Signature,
+    signer::{null_signer::NullSigner, Signer},
+    system_program,
+    transaction::Transaction,
 };
 
 use solana_account_decoder::UiAccountEncoding;
@@ -67,7 +78,7 @@ pub struct InputParams {
     pub deployed_slot: u64,
 }
 
-#[derive(PartialEq)]
+#[derive(Clone, Debug, PartialEq)]
 pub enum OtterVerifyInstructions {
     Initialize,
     Update,
@@ -91,23 +102,126 @@ fn create_ix_data(params: &InputParams, ix: &OtterVerifyInstructions) -> Vec<u8>
     data
 }
 
-fn get_keypair_from_path(path: &str) -> anyhow::Result<Keypair> {
-    solana_clap_utils::keypair::keypair_from_path(&Default::default(), &path, "keypair", false)
-        .map_err(|err| anyhow!("Unable to get signer from path: {}", err))
+// Supports file paths, `prompt://`, and hardware wallet URIs like `usb://ledger?key=0`.
+fn get_signer_from_path(path: &str) -> anyhow::Result<Box<dyn Signer>> {
+    let mut wallet_manager: Option<std::rc::Rc<RemoteWalletManager>> = None;
+    solana_clap_utils::keypair::signer_from_path(
+        &Default::default(),
+        path,
+        "keypair",
+        &mut wallet_manager,
+    )
+    .map_err(|err| anyhow!("Unable to get signer from path: {}", err))
 }
 
-fn get_user_config() -> anyhow::Result<(Keypair, RpcClient)> {
+fn parse_signer_arg(raw: &str) -> anyhow::Result<(Pubkey, Signature)> {
+    let (pubkey, signature) = raw.split_once('=').ok_or_else(|| {
+        anyhow!(
+            "Invalid --signer value `{}`, expected <pubkey>=<signature>",
+            raw
+        )
+    })?;
+    let pubkey = Pubkey::from_str(pubkey)
+        .map_err(|err| anyhow!("Invalid pubkey in --signer value: {}", err))?;
+    let signature = Signature::from_str(signature)
+        .map_err(|err| anyhow!("Invalid signature in --signer value: {}", err))?;
+    Ok((pubkey, signature))
+}
+
+fn print_sign_only_output(tx: &Transaction, blockhash: Hash) {
+    println!("Blockhash: {}", blockhash);
+    println!(
+        "Unsigned message (base58): {}",
+        bs58::encode(tx.message.serialize()).into_string()
+    );
+    for (pubkey, signature) in tx
+        .message
+        .account_keys
+        .iter()
+        .zip(tx.signatures.iter())
+        .filter(|(_, signature)| **signature != Signature::default())
+    {
+        println!("Signer: {}={}", pubkey, signature);
+    }
+}
+
+#[derive(Clone, Default)]
+pub struct ComputeBudgetConfig {
+    pub unit_price: Option<u64>,
+    pub unit_limit: Option<u32>,
+    // When set, ignore `unit_price` and derive it from recent prioritization fees.
+    pub auto: bool,
+}
+
+fn get_auto_priority_fee(rpc_client: &RpcClient, writable_accounts: &[Pubkey]) -> anyhow::Result<u64> {
+    let mut fees: Vec<u64> = rpc_client
+        .get_recent_prioritization_fees(writable_accounts)?
+        .into_iter()
+        .map(|fee| fee.prioritization_fee)
+        .collect();
+
+    if fees.is_empty() {
+        return Ok(0);
+    }
+
+    fees.sort_unstable();
+    let index = (fees.len() * 3 / 4).min(fees.len() - 1);
+    Ok(fees[index])
+}
+
+const TPU_POLL_INTERVAL: std::time::Duration = std::time::Duration::from_millis(400);
+
+// Bounded solely by the blockhash's validity window (checked every round),
+// not by a fixed attempt count, since that window (tens of seconds) is what
+// actually determines how long a resend can still land.
+fn send_transaction_via_tpu(
+    rpc_url: &str,
+    websocket_url: &str,
+    tx: &Transaction,
+) -> anyhow::Result<Signature> {
+    let rpc_client = Arc::new(RpcClient::new(rpc_url.to_string()));
+    let tpu_client = TpuClient::new(rpc_client.clone(), websocket_url, TpuClientConfig::default())
+        .map_err(|err| anyhow!("Unable to connect to the TPU: {}", err))?;
+
+    let signature = tx.signatures[0];
+    let recent_blockhash = tx.message.recent_blockhash;
+
+    loop {
+        tpu_client.send_transaction(tx);
+        std::thread::sleep(TPU_POLL_INTERVAL);
+
+        if let Ok(Some(Ok(()))) = rpc_client.get_signature_status(&signature) {
+            return Ok(signature);
+        }
+
+        if !rpc_client
+            .is_blockhash_valid(&recent_blockhash, CommitmentConfig::processed())
+            .unwrap_or(false)
+        {
+            return Err(anyhow!(
+                "Transaction {} was not confirmed before blockhash {} expired",
+                signature,
+                recent_blockhash
+            ));
+        }
+    }
+}
+
+fn load_cli_config() -> anyhow::Result<Config> {
     let config_file = solana_cli_config::CONFIG_FILE
         .as_ref()
         .ok_or_else(|| anyhow!("Unable to get config file path"))?;
-    let cli_config: Config = Config::load(config_file)?;
-
-    let signer = get_keypair_from_path(&cli_config.keypair_path)?;
+    Config::load(config_file).map_err(|err| anyhow!("Unable to load CLI config: {}", err))
+}
 
+fn get_user_config() -> anyhow::Result<(Box<dyn Signer>, RpcClient)> {
+    let cli_config = load_cli_config()?;
+    let signer = get_signer_from_path(&cli_config.keypair_path)?;
     let rpc_client = RpcClient::new(cli_config.json_rpc_url.clone());
     Ok((signer, rpc_client))
 }
 
+#[allow(clippy::too_many_arguments)]
 fn process_otter_verify_ixs(
     params: &InputParams,
     pda_account: Pubkey,
@@ -115,14 +229,46 @@ fn process_otter_verify_ixs(
     instruction: OtterVerifyInstructions,
     rpc_client: RpcClient,
     path_to_keypair: Option<String>,
+    resolved_signer: Option<&dyn Signer>,
+    blockhash: Option<Hash>,
+    sign_only: bool,
+    presigned: &[(Pubkey, Signature)],
+    compute_budget: &ComputeBudgetConfig,
+    use_tpu: bool,
+    websocket_url: Option<String>,
 ) -> anyhow::Result<()> {
-    let user_config = get_user_config()?;
-    let signer = if let Some(path_to_keypair) = path_to_keypair {
-        get_keypair_from_path(&path_to_keypair)?
+    if sign_only && !presigned.is_empty() {
+        return Err(anyhow!(
+            "--sign-only cannot be combined with --signer; pass one or the other"
+        ));
+    }
+
+    // The submitting machine may not hold a keypair at all when it's only
+    // combining signatures gathered from an offline signer; in that case the
+    // first presented signer is the fee payer, and we must not touch the
+    // default CLI keypair (it may not exist, or may be a hardware wallet).
+    // When a path is given, resolve it once here and reuse it below instead
+    // of reconnecting to the same file/hardware wallet a second time. A
+    // caller that already resolved a signer (e.g. a batch operation reusing
+    // one signer across many PDAs) passes it directly via `resolved_signer`.
+    let mut path_signer: Option<Box<dyn Signer>> = None;
+    let mut default_signer: Option<Box<dyn Signer>> = None;
+    let signer_pubkey = if let Some(signer) = resolved_signer {
+        signer.pubkey()
+    } else if let Some(path_to_keypair) = path_to_keypair.as_deref() {
+        let signer = get_signer_from_path(path_to_keypair)?;
+        let pubkey = signer.pubkey();
+        path_signer = Some(signer);
+        pubkey
+    } else if let Some((pubkey, _)) = presigned.first() {
+        *pubkey
     } else {
-        user_config.0
+        let signer = get_user_config()?.0;
+        let pubkey = signer.pubkey();
+        default_signer = Some(signer);
+        pubkey
     };
-    let signer_pubkey = signer.pubkey();
+
     let connection = rpc_client;
 
     let ix_data = if instruction != OtterVerifyInstructions::Close {
@@ -147,22 +293,91 @@ fn process_otter_verify_ixs(
         &ix_data,
         accounts_meta_vec,
     );
-    let message = Message::new(&[ix], Some(&signer_pubkey));
+
+    let mut instructions = Vec::new();
+    if let Some(unit_limit) = compute_budget.unit_limit {
+        instructions.push(ComputeBudgetInstruction::set_compute_unit_limit(unit_limit));
+    }
+    let unit_price = if compute_budget.auto {
+        Some(get_auto_priority_fee(
+            &connection,
+            &[pda_account, program_address],
+        )?)
+    } else {
+        compute_budget.unit_price
+    };
+    if let Some(unit_price) = unit_price {
+        instructions.push(ComputeBudgetInstruction::set_compute_unit_price(unit_price));
+    }
+    instructions.push(ix);
+
+    let message = Message::new(&instructions, Some(&signer_pubkey));
 
     let mut tx = Transaction::new_unsigned(message);
 
-    tx.sign(&[&signer], connection.get_latest_blockhash()?);
+    let blockhash = match blockhash {
+        Some(blockhash) => blockhash,
+        None => connection.get_latest_blockhash()?,
+    };
+
+    if sign_only {
+        let signer: &dyn Signer = resolved_signer
+            .or_else(|| path_signer.as_deref())
+            .or_else(|| default_signer.as_deref())
+            .expect("presigned empty implies a local signer was resolved above");
+        tx.try_partial_sign(&[signer], blockhash)?;
+        print_sign_only_output(&tx, blockhash);
+        return Ok(());
+    }
 
-    let tx_id = connection
-        .send_and_confirm_transaction_with_spinner(&tx)
-        .map_err(|err| {
-            println!("{:?}", err);
-            anyhow!("Failed to send transaction to the network.")
-        })?;
+    if presigned.is_empty() {
+        let signer: &dyn Signer = resolved_signer
+            .or_else(|| path_signer.as_deref())
+            .or_else(|| default_signer.as_deref())
+            .expect("presigned empty implies a local signer was resolved above");
+        tx.sign(&[signer], blockhash);
+    } else {
+        let null_signers: Vec<NullSigner> = presigned
+            .iter()
+            .map(|(pubkey, _)| NullSigner::new(pubkey))
+            .collect();
+        let signer_refs: Vec<&dyn Signer> = null_signers
+            .iter()
+            .map(|signer| signer as &dyn Signer)
+            .collect();
+        tx.try_partial_sign(&signer_refs[..], blockhash)?;
+
+        for (pubkey, signature) in presigned {
+            let index = tx
+                .message
+                .account_keys
+                .iter()
+                .position(|key| key == pubkey)
+                .ok_or_else(|| anyhow!("Signer {} is not part of this transaction", pubkey))?;
+            tx.signatures[index] = *signature;
+        }
+
+        if tx.verify().is_err() {
+            return Err(anyhow!(
+                "Transaction is missing required signatures; supply every `--signer <pubkey>=<signature>` pair"
+            ));
+        }
+    }
+
+    let tx_id = match websocket_url.filter(|_| use_tpu) {
+        Some(websocket_url) => send_transaction_via_tpu(&connection.url(), &websocket_url, &tx)?,
+        None => connection
+            .send_and_confirm_transaction_with_spinner(&tx)
+            .map_err(|err| {
+                println!("{:?}", err);
+                anyhow!("Failed to send transaction to the network.")
+            })?,
+    };
     println!("Program uploaded successfully. Transaction ID: {}", tx_id);
     Ok(())
 }
 
+#[allow(clippy::too_many_arguments)]
 pub async fn upload_program(
     git_url: String,
     commit: &Option<String>,
@@ -171,6 +386,12 @@ pub async fn upload_program(
     connection_url: Option<String>,
     skip_prompt: bool,
     path_to_keypair: Option<String>,
+    blockhash: Option<String>,
+    sign_only: bool,
+    signers: Vec<String>,
+    compute_budget: ComputeBudgetConfig,
+    use_tpu: bool,
+    websocket_url: Option<String>,
 ) -> anyhow::Result<()> {
     if skip_prompt
         || prompt_user_input(
@@ -181,8 +402,20 @@ pub async fn upload_program(
 
         let cli_config = get_user_config()?;
 
+        let presigned = signers
+            .iter()
+            .map(|signer| parse_signer_arg(signer))
+            .collect::<anyhow::Result<Vec<(Pubkey, Signature)>>>()?;
+
+        let blockhash = blockhash
+            .map(|blockhash| Hash::from_str(&blockhash))
+            .transpose()
+            .map_err(|err| anyhow!("Invalid --blockhash: {}", err))?;
+
         let signer_pubkey: Pubkey = if let Some(ref path_to_keypair) = path_to_keypair {
-            get_keypair_from_path(&path_to_keypair)?.pubkey()
+            get_signer_from_path(path_to_keypair)?.pubkey()
+        } else if let Some((pubkey, _)) = presigned.first() {
+            *pubkey
         } else {
             cli_config.0.pubkey()
         };
@@ -238,6 +471,13 @@ pub async fn upload_program(
                 OtterVerifyInstructions::Update,
                 connection,
                 path_to_keypair,
+                None,
+                blockhash,
+                sign_only,
+                &presigned,
+                &compute_budget,
+                use_tpu,
+                websocket_url,
             )?;
         } else if connection.get_account(&pda_account_2).is_ok() {
             let wanna_create_new_pda = skip_prompt || prompt_user_input(
@@ -251,6 +491,13 @@ pub async fn upload_program(
                     OtterVerifyInstructions::Initialize,
                     connection,
                     path_to_keypair,
+                    None,
+                    blockhash,
+                    sign_only,
+                    &presigned,
+                    &compute_budget,
+                    use_tpu,
+                    websocket_url,
                 )?;
             }
             return Ok(());
@@ -263,6 +510,13 @@ pub async fn upload_program(
                 OtterVerifyInstructions::Initialize,
                 connection,
                 path_to_keypair,
+                None,
+                blockhash,
+                sign_only,
+                &presigned,
+                &compute_budget,
+                use_tpu,
+                websocket_url,
             )?;
         }
     } else {
@@ -272,13 +526,41 @@ pub async fn upload_program(
     Ok(())
 }
 
-pub async fn process_close(program_address: Pubkey) -> anyhow::Result<()> {
-    let user_config = get_user_config()?;
-    let signer = user_config.0;
-    let signer_pubkey = signer.pubkey();
-    let connection = user_config.1;
+#[allow(clippy::too_many_arguments)]
+pub async fn process_close(
+    program_address: Pubkey,
+    path_to_keypair: Option<String>,
+    blockhash: Option<String>,
+    sign_only: bool,
+    signers: Vec<String>,
+    compute_budget: ComputeBudgetConfig,
+    use_tpu: bool,
+    websocket_url: Option<String>,
+) -> anyhow::Result<()> {
+    let cli_config = load_cli_config()?;
+    let connection = RpcClient::new(cli_config.json_rpc_url.clone());
     let rpc_url = connection.url();
 
+    let presigned = signers
+        .iter()
+        .map(|signer| parse_signer_arg(signer))
+        .collect::<anyhow::Result<Vec<(Pubkey, Signature)>>>()?;
+
+    let blockhash = blockhash
+        .map(|blockhash| Hash::from_str(&blockhash))
+        .transpose()
+        .map_err(|err| anyhow!("Invalid --blockhash: {}", err))?;
+
+    // Only resolve a signer when we actually need one locally; a pure
+    // signature-combiner invocation may have no keypair configured at all.
+    let signer_pubkey = if let Some(ref path_to_keypair) = path_to_keypair {
+        get_signer_from_path(path_to_keypair)?.pubkey()
+    } else if let Some((pubkey, _)) = presigned.first() {
+        *pubkey
+    } else {
+        get_signer_from_path(&cli_config.keypair_path)?.pubkey()
+    };
+
     let last_deployed_slot = get_last_deployed_slot(&rpc_url, &program_address.to_string())
         .await
         .map_err(|err| anyhow!("Unable to get last deployed slot: {}", err))?;
@@ -306,7 +588,14 @@ pub async fn process_close(program_address: Pubkey) -> anyhow::Result<()> {
             program_address,
             OtterVerifyInstructions::Close,
             connection,
+            path_to_keypair,
             None,
+            blockhash,
+            sign_only,
+            &presigned,
+            &compute_budget,
+            use_tpu,
+            websocket_url,
         )?;
     } else {
         return Err(anyhow!(
@@ -357,3 +646,209 @@ pub async fn get_all_pdas_available(
 
     Ok(pdas)
 }
+
+async fn get_matching_pdas(
+    connection: &RpcClient,
+    program_id: &Pubkey,
+    filter_signer: Pubkey,
+) -> anyhow::Result<Vec<(Pubkey, OtterBuildParams)>> {
+    let pdas = get_all_pdas_available(connection, program_id).await?;
+    Ok(pdas
+        .into_iter()
+        .filter(|(_, build_params)| build_params.signer == filter_signer)
+        .collect())
+}
+
+// Processes every matching PDA sequentially, accumulating per-PDA errors
+// instead of aborting on the first failure.
+#[allow(clippy::too_many_arguments)]
+async fn process_batch(
+    program_id: Pubkey,
+    connection_url: Option<String>,
+    path_to_keypair: Option<String>,
+    use_otter_signer: bool,
+    compute_budget: ComputeBudgetConfig,
+    use_tpu: bool,
+    websocket_url: Option<String>,
+    instruction: OtterVerifyInstructions,
+) -> anyhow::Result<()> {
+    // Only loads the config file, not a signer, so it's safe even when
+    // `path_to_keypair` means the default keypair is never touched.
+    let cli_config = load_cli_config()?;
+
+    let connection = match connection_url.as_deref() {
+        Some("m") => RpcClient::new("https://api.mainnet-beta.solana.com"),
+        Some("d") => RpcClient::new("https://api.devnet.solana.com"),
+        Some("l") => RpcClient::new("http://localhost:8899"),
+        Some(url) => RpcClient::new(url),
+        None => RpcClient::new(cli_config.json_rpc_url.clone()),
+    };
+    let rpc_url = connection.url();
+
+    // Resolved once and reused for every PDA below instead of letting
+    // `process_otter_verify_ixs` re-derive it per PDA.
+    let signer: Box<dyn Signer> = if let Some(ref path_to_keypair) = path_to_keypair {
+        get_signer_from_path(path_to_keypair)?
+    } else {
+        get_signer_from_path(&cli_config.keypair_path)?
+    };
+    let signer_pubkey = signer.pubkey();
+
+    let filter_signer = if use_otter_signer {
+        Pubkey::from_str(OTTER_SIGNER)?
+    } else {
+        signer_pubkey
+    };
+
+    let matching = get_matching_pdas(&connection, &program_id, filter_signer).await?;
+
+    if matching.is_empty() {
+        println!("No PDAs found for signer {}.", filter_signer);
+        return Ok(());
+    }
+
+    println!(
+        "Found {} PDA(s) owned by {} to process.",
+        matching.len(),
+        filter_signer
+    );
+
+    let mut failures = vec![];
+    for (index, (pda_account, build_params)) in matching.iter().enumerate() {
+        println!(
+            "[{}/{}] {:?} PDA {} (program {})",
+            index + 1,
+            matching.len(),
+            instruction,
+            pda_account,
+            build_params.address
+        );
+
+        let result = process_one_pda(
+            &rpc_url,
+            *pda_account,
+            build_params,
+            &instruction,
+            signer.as_ref(),
+            &compute_budget,
+            use_tpu,
+            websocket_url.clone(),
+        )
+        .await;
+
+        if let Err(err) = result {
+            println!("  Failed: {}", err);
+            failures.push((*pda_account, err));
+        }
+    }
+
+    if failures.is_empty() {
+        println!("Successfully processed all {} PDA(s).", matching.len());
+        Ok(())
+    } else {
+        let failed_pdas: Vec<String> = failures.iter().map(|(pda, _)| pda.to_string()).collect();
+        println!(
+            "Processed {}/{} PDA(s); failed: {}",
+            matching.len() - failures.len(),
+            matching.len(),
+            failed_pdas.join(", ")
+        );
+        Err(anyhow!("{} PDA(s) failed to process", failures.len()))
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn process_one_pda(
+    rpc_url: &str,
+    pda_account: Pubkey,
+    build_params: &OtterBuildParams,
+    instruction: &OtterVerifyInstructions,
+    signer: &dyn Signer,
+    compute_budget: &ComputeBudgetConfig,
+    use_tpu: bool,
+    websocket_url: Option<String>,
+) -> anyhow::Result<()> {
+    let deployed_slot = get_last_deployed_slot(rpc_url, &build_params.address.to_string())
+        .await
+        .map_err(|err| anyhow!("Unable to get last deployed slot: {}", err))?;
+
+    let input_params = if *instruction == OtterVerifyInstructions::Close {
+        InputParams {
+            version: "".to_string(),
+            git_url: "".to_string(),
+            commit: "".to_string(),
+            args: vec![],
+            deployed_slot,
+        }
+    } else {
+        InputParams {
+            version: build_params.version.clone(),
+            git_url: build_params.git_url.clone(),
+            commit: build_params.commit.clone(),
+            args: build_params.args.clone(),
+            deployed_slot,
+        }
+    };
+
+    process_otter_verify_ixs(
+        &input_params,
+        pda_account,
+        build_params.address,
+        instruction.clone(),
+        RpcClient::new(rpc_url.to_string()),
+        None,
+        Some(signer),
+        None,
+        false,
+        &[],
+        compute_budget,
+        use_tpu,
+        websocket_url,
+    )
+}
+
+#[allow(clippy::too_many_arguments)]
+pub async fn process_reupload_all(
+    program_id: Pubkey,
+    connection_url: Option<String>,
+    path_to_keypair: Option<String>,
+    use_otter_signer: bool,
+    compute_budget: ComputeBudgetConfig,
+    use_tpu: bool,
+    websocket_url: Option<String>,
+) -> anyhow::Result<()> {
+    process_batch(
+        program_id,
+        connection_url,
+        path_to_keypair,
+        use_otter_signer,
+        compute_budget,
+        use_tpu,
+        websocket_url,
+        OtterVerifyInstructions::Update,
+    )
+    .await
+}
+
+#[allow(clippy::too_many_arguments)]
+pub async fn process_close_all(
+    program_id: Pubkey,
+    connection_url: Option<String>,
+    path_to_keypair: Option<String>,
+    use_otter_signer: bool,
+    compute_budget: ComputeBudgetConfig,
+    use_tpu: bool,
+    websocket_url: Option<String>,
+) -> anyhow::Result<()> {
+    process_batch(
+        program_id,
+        connection_url,
+        path_to_keypair,
+        use_otter_signer,
+        compute_budget,
+        use_tpu,
+        websocket_url,
+        OtterVerifyInstructions::Close,
+    )
+    .await
+}